@@ -0,0 +1,315 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+//
+// Permission is hereby granted, free of charge, to any
+// person obtaining a copy of this software and associated
+// documentation files (the "Software"), to deal in the
+// Software without restriction, including without
+// limitation the rights to use, copy, modify, merge,
+// publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software
+// is furnished to do so, subject to the following
+// conditions:
+//
+// The above copyright notice and this permission notice
+// shall be included in all copies or substantial portions
+// of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF
+// ANY KIND, EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED
+// TO THE WARRANTIES OF MERCHANTABILITY, FITNESS FOR A
+// PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT
+// SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY
+// CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR
+// IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! CORS handling.
+
+use crate::access_control::matcher::Pattern;
+use hyper::Method;
+
+/// Origin protection.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AccessControlAllowOrigin {
+	/// Allow that exact origin.
+	Value(String),
+	/// Allow any origin.
+	Any,
+	/// Allow origin that sent `null` header.
+	Null,
+	/// Allow any origin matching a glob pattern, e.g. `https://*.example.com`.
+	///
+	/// A match is never reflected back as-is: the concrete requesting origin is echoed in
+	/// `Access-Control-Allow-Origin` instead, as required by the CORS specification.
+	Pattern(Pattern),
+}
+
+impl AccessControlAllowOrigin {
+	fn matches(&self, origin: &str) -> bool {
+		match self {
+			AccessControlAllowOrigin::Value(ref val) => val == origin,
+			// `null` is sent by browsers for opaque origins (e.g. sandboxed iframes, `file://`
+			// pages) and must only ever be matched explicitly, never by a wildcard pattern.
+			AccessControlAllowOrigin::Null => origin == "null",
+			AccessControlAllowOrigin::Any => true,
+			AccessControlAllowOrigin::Pattern(ref pattern) => origin != "null" && pattern.matches(origin),
+		}
+	}
+}
+
+/// Headers protection.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AccessControlAllowHeaders {
+	/// Any header is allowed.
+	Any,
+	/// Only specified headers are allowed.
+	Only(Vec<String>),
+}
+
+/// Methods protection.
+///
+/// Mirrors [`AccessControlAllowHeaders`]: either any HTTP method is
+/// accepted, or only a fixed set of methods may be used against the
+/// JSON-RPC endpoint.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AccessControlAllowMethods {
+	/// Any method is allowed.
+	Any,
+	/// Only specified methods are allowed.
+	Only(Vec<Method>),
+}
+
+/// Result of CORS validation.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AllowCors<T> {
+	/// CORS header is not required. The request is not a cross-origin one.
+	NotRequired,
+	/// CORS header is invalid.
+	Invalid,
+	/// CORS header is valid and the value to use in the response is `T`.
+	Ok(T),
+}
+
+impl<T> AllowCors<T> {
+	/// Maps the `Ok` variant, leaving `NotRequired`/`Invalid` untouched.
+	pub fn map<F, U>(self, f: F) -> AllowCors<U>
+	where
+		F: FnOnce(T) -> U,
+	{
+		match self {
+			AllowCors::NotRequired => AllowCors::NotRequired,
+			AllowCors::Invalid => AllowCors::Invalid,
+			AllowCors::Ok(val) => AllowCors::Ok(f(val)),
+		}
+	}
+}
+
+/// Strips the `scheme://` prefix from an `Origin` header value, leaving just its authority
+/// (host, and port if present) so it can be compared against a `Host` header.
+fn origin_authority(origin: &str) -> &str {
+	match origin.find("://") {
+		Some(pos) => &origin[pos + 3..],
+		None => origin,
+	}
+}
+
+/// Validates the `Origin` header against the configured allow-list and
+/// returns the value to echo back in `Access-Control-Allow-Origin`.
+pub fn get_cors_allow_origin(
+	origin: Option<&str>,
+	host: Option<&str>,
+	allowed: &Option<Vec<AccessControlAllowOrigin>>,
+) -> AllowCors<AccessControlAllowOrigin> {
+	let origin = match origin {
+		Some(origin) => origin,
+		None => return AllowCors::NotRequired,
+	};
+
+	// Request from the same host as the server: no CORS header is required. The `Origin`
+	// header is scheme-qualified (e.g. `http://example.com`) while `Host` is not, so compare
+	// against the origin's authority rather than the raw header.
+	if let Some(host) = host {
+		if origin_authority(origin) == host {
+			return AllowCors::NotRequired;
+		}
+	}
+
+	match allowed {
+		Some(list) => match list.iter().find(|allowed| allowed.matches(origin)) {
+			Some(AccessControlAllowOrigin::Any) => AllowCors::Ok(AccessControlAllowOrigin::Any),
+			Some(_matched) => AllowCors::Ok(AccessControlAllowOrigin::Value(origin.to_owned())),
+			None => AllowCors::Invalid,
+		},
+		None => AllowCors::Invalid,
+	}
+}
+
+/// Validates the `Access-Control-Request-Headers` header against the
+/// configured allow-list and builds the value to use in
+/// `Access-Control-Allow-Headers`.
+pub fn get_cors_allow_headers<'a, F>(
+	headers: impl Iterator<Item = &'a str>,
+	requested_headers: impl Iterator<Item = &'a str>,
+	allowed_headers: &AccessControlAllowHeaders,
+	to_header_value: F,
+) -> AllowCors<hyper::header::HeaderValue>
+where
+	F: Fn(&str) -> hyper::header::HeaderValue,
+{
+	let requested: Vec<&str> = requested_headers.map(str::trim).filter(|header| !header.is_empty()).collect();
+
+	if requested.is_empty() {
+		return AllowCors::NotRequired;
+	}
+
+	match allowed_headers {
+		AccessControlAllowHeaders::Any => AllowCors::Ok(to_header_value(&requested.join(", "))),
+		AccessControlAllowHeaders::Only(allowed) => {
+			// Headers that are already present on the request are implicitly allowed,
+			// in addition to the ones explicitly configured.
+			let present: std::collections::HashSet<String> = headers.map(str::to_ascii_lowercase).collect();
+			let allowed_lower: Vec<String> = allowed.iter().map(|header| header.to_ascii_lowercase()).collect();
+
+			let all_allowed = requested.iter().all(|header| {
+				let lower = header.to_ascii_lowercase();
+				allowed_lower.contains(&lower) || present.contains(&lower)
+			});
+
+			if all_allowed {
+				AllowCors::Ok(to_header_value(&allowed.join(", ")))
+			} else {
+				AllowCors::Invalid
+			}
+		}
+	}
+}
+
+/// Validates the `Access-Control-Request-Method` header, sent on a preflight
+/// request, against the configured allow-list.
+pub fn get_cors_allow_method(
+	requested_method: Option<&str>,
+	allowed_methods: &AccessControlAllowMethods,
+) -> AllowCors<Method> {
+	let requested_method = match requested_method {
+		Some(method) => method,
+		None => return AllowCors::NotRequired,
+	};
+
+	let requested_method = match requested_method.parse::<Method>() {
+		Ok(method) => method,
+		Err(_) => return AllowCors::Invalid,
+	};
+
+	match allowed_methods {
+		AccessControlAllowMethods::Any => AllowCors::Ok(requested_method),
+		AccessControlAllowMethods::Only(allowed) if allowed.contains(&requested_method) => {
+			AllowCors::Ok(requested_method)
+		}
+		AccessControlAllowMethods::Only(_) => AllowCors::Invalid,
+	}
+}
+
+/// Builds the value to use in `Access-Control-Allow-Methods` for a preflight response.
+pub fn allow_methods_header_value(allowed_methods: &AccessControlAllowMethods) -> hyper::header::HeaderValue {
+	match allowed_methods {
+		AccessControlAllowMethods::Any => hyper::header::HeaderValue::from_static("*"),
+		AccessControlAllowMethods::Only(allowed) => {
+			let methods = allowed.iter().map(Method::as_str).collect::<Vec<_>>().join(", ");
+			hyper::header::HeaderValue::from_str(&methods).unwrap_or_else(|_| hyper::header::HeaderValue::from_static("*"))
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn same_host_does_not_require_cors() {
+		let origin = get_cors_allow_origin(Some("http://example.com"), Some("example.com"), &None);
+		assert_eq!(origin, AllowCors::NotRequired);
+	}
+
+	#[test]
+	fn no_origin_does_not_require_cors() {
+		let origin = get_cors_allow_origin(None, Some("example.com"), &None);
+		assert_eq!(origin, AllowCors::NotRequired);
+	}
+
+	#[test]
+	fn unlisted_origin_is_invalid() {
+		let allowed = Some(vec![AccessControlAllowOrigin::Value("http://parity.io".into())]);
+		let origin = get_cors_allow_origin(Some("http://evil.com"), Some("example.com"), &allowed);
+		assert_eq!(origin, AllowCors::Invalid);
+	}
+
+	#[test]
+	fn listed_origin_is_allowed() {
+		let allowed = Some(vec![AccessControlAllowOrigin::Value("http://parity.io".into())]);
+		let origin = get_cors_allow_origin(Some("http://parity.io"), Some("example.com"), &allowed);
+		assert_eq!(origin, AllowCors::Ok(AccessControlAllowOrigin::Value("http://parity.io".into())));
+	}
+
+	#[test]
+	fn pattern_origin_matches_subdomain_and_reflects_concrete_origin() {
+		let allowed = Some(vec![AccessControlAllowOrigin::Pattern(Pattern::new("https://*.example.com"))]);
+		let origin = get_cors_allow_origin(Some("https://foo.example.com"), Some("example.com"), &allowed);
+		assert_eq!(origin, AllowCors::Ok(AccessControlAllowOrigin::Value("https://foo.example.com".into())));
+	}
+
+	#[test]
+	fn pattern_origin_is_scheme_and_port_sensitive() {
+		let allowed = Some(vec![AccessControlAllowOrigin::Pattern(Pattern::new("https://*.example.com"))]);
+
+		// Wrong scheme.
+		let origin = get_cors_allow_origin(Some("http://foo.example.com"), Some("host"), &allowed);
+		assert_eq!(origin, AllowCors::Invalid);
+
+		// Unexpected port.
+		let origin = get_cors_allow_origin(Some("https://foo.example.com:8080"), Some("host"), &allowed);
+		assert_eq!(origin, AllowCors::Invalid);
+	}
+
+	#[test]
+	fn pattern_origin_never_matches_null_or_unrelated_hosts() {
+		let allowed = Some(vec![AccessControlAllowOrigin::Pattern(Pattern::new("https://*.example.com"))]);
+
+		let origin = get_cors_allow_origin(Some("null"), Some("host"), &allowed);
+		assert_eq!(origin, AllowCors::Invalid);
+
+		let origin = get_cors_allow_origin(Some("https://example.com.evil.com"), Some("host"), &allowed);
+		assert_eq!(origin, AllowCors::Invalid);
+	}
+
+	#[test]
+	fn no_requested_headers_does_not_require_cors() {
+		let allowed = AccessControlAllowHeaders::Any;
+		let result = get_cors_allow_headers(std::iter::empty(), std::iter::empty(), &allowed, |v| {
+			hyper::header::HeaderValue::from_str(v).unwrap()
+		});
+		assert_eq!(result, AllowCors::NotRequired);
+	}
+
+	#[test]
+	fn only_allows_listed_headers() {
+		let allowed = AccessControlAllowHeaders::Only(vec!["content-type".into()]);
+		let result = get_cors_allow_headers(std::iter::empty(), vec!["x-unknown"].into_iter(), &allowed, |v| {
+			hyper::header::HeaderValue::from_str(v).unwrap()
+		});
+		assert_eq!(result, AllowCors::Invalid);
+	}
+
+	#[test]
+	fn no_requested_method_does_not_require_cors() {
+		let result = get_cors_allow_method(None, &AccessControlAllowMethods::Any);
+		assert_eq!(result, AllowCors::NotRequired);
+	}
+
+	#[test]
+	fn only_allows_listed_methods() {
+		let allowed = AccessControlAllowMethods::Only(vec![Method::POST]);
+		assert_eq!(get_cors_allow_method(Some("POST"), &allowed), AllowCors::Ok(Method::POST));
+		assert_eq!(get_cors_allow_method(Some("GET"), &allowed), AllowCors::Invalid);
+	}
+}
@@ -0,0 +1,100 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+//
+// Permission is hereby granted, free of charge, to any
+// person obtaining a copy of this software and associated
+// documentation files (the "Software"), to deal in the
+// Software without restriction, including without
+// limitation the rights to use, copy, modify, merge,
+// publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software
+// is furnished to do so, subject to the following
+// conditions:
+//
+// The above copyright notice and this permission notice
+// shall be included in all copies or substantial portions
+// of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF
+// ANY KIND, EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED
+// TO THE WARRANTIES OF MERCHANTABILITY, FITNESS FOR A
+// PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT
+// SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY
+// CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR
+// IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! Validation of the HTTP `Host` header.
+
+use crate::access_control::matcher::Pattern;
+
+/// Define which hosts are allowed to connect.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AllowHosts {
+	/// Any hosts are accepted.
+	Any,
+	/// Only listed hosts are allowed.
+	Only(Vec<Host>),
+}
+
+/// A single allowed host, optionally containing a `*` wildcard (e.g. `*.example.com:8080`).
+#[derive(Clone, Debug)]
+pub struct Host {
+	matcher: Pattern,
+}
+
+impl Host {
+	/// Create a new `Host` from a string such as `localhost:8080` or `*.example.com`.
+	pub fn new(host: impl AsRef<str>) -> Self {
+		Host { matcher: Pattern::new(host.as_ref()) }
+	}
+
+	fn matches(&self, value: &str) -> bool {
+		self.matcher.matches(value)
+	}
+}
+
+impl<T: AsRef<str>> From<T> for Host {
+	fn from(host: T) -> Self {
+		Host::new(host)
+	}
+}
+
+impl PartialEq for Host {
+	fn eq(&self, other: &Self) -> bool {
+		self.matcher == other.matcher
+	}
+}
+
+impl Eq for Host {}
+
+/// Returns `true` when `host` is allowed to connect, given the `allowed` hosts.
+pub fn is_host_valid(host: Option<&str>, allowed: &AllowHosts) -> bool {
+	match allowed {
+		AllowHosts::Any => true,
+		AllowHosts::Only(allowed_hosts) => match host {
+			Some(host) => allowed_hosts.iter().any(|h| h.matches(host)),
+			None => false,
+		},
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn any_host_is_always_valid() {
+		assert!(is_host_valid(Some("example.com"), &AllowHosts::Any));
+		assert!(is_host_valid(None, &AllowHosts::Any));
+	}
+
+	#[test]
+	fn only_listed_hosts_are_valid() {
+		let allowed = AllowHosts::Only(vec![Host::new("localhost:8080"), Host::new("example.com")]);
+		assert!(is_host_valid(Some("localhost:8080"), &allowed));
+		assert!(is_host_valid(Some("example.com"), &allowed));
+		assert!(!is_host_valid(Some("evil.com"), &allowed));
+		assert!(!is_host_valid(None, &allowed));
+	}
+}
@@ -0,0 +1,106 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+//
+// Permission is hereby granted, free of charge, to any
+// person obtaining a copy of this software and associated
+// documentation files (the "Software"), to deal in the
+// Software without restriction, including without
+// limitation the rights to use, copy, modify, merge,
+// publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software
+// is furnished to do so, subject to the following
+// conditions:
+//
+// The above copyright notice and this permission notice
+// shall be included in all copies or substantial portions
+// of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF
+// ANY KIND, EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED
+// TO THE WARRANTIES OF MERCHANTABILITY, FITNESS FOR A
+// PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT
+// SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY
+// CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR
+// IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! Simple glob-style pattern matching, used to match hosts and CORS origins
+//! that contain a `*` wildcard.
+
+/// A pattern that may contain `*` wildcards, each matching any sequence of
+/// characters (including none).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Pattern {
+	parts: Vec<String>,
+}
+
+impl Pattern {
+	/// Create a new pattern from the given string.
+	pub fn new(pattern: &str) -> Self {
+		Pattern { parts: pattern.split('*').map(str::to_owned).collect() }
+	}
+
+	/// Returns whether `value` matches this pattern.
+	pub fn matches(&self, value: &str) -> bool {
+		let (first, rest) = match self.parts.split_first() {
+			Some(parts) => parts,
+			None => return value.is_empty(),
+		};
+
+		if rest.is_empty() {
+			return value == first;
+		}
+
+		let mut value = match value.strip_prefix(first.as_str()) {
+			Some(value) => value,
+			None => return false,
+		};
+
+		let (last, middle) = rest.split_last().expect("rest is non-empty; qed");
+		for part in middle {
+			if part.is_empty() {
+				continue;
+			}
+			value = match value.find(part.as_str()) {
+				Some(pos) => &value[pos + part.len()..],
+				None => return false,
+			};
+		}
+
+		value.ends_with(last.as_str())
+	}
+}
+
+impl<T: AsRef<str>> From<T> for Pattern {
+	fn from(pattern: T) -> Self {
+		Pattern::new(pattern.as_ref())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::Pattern;
+
+	#[test]
+	fn matches_exact_string() {
+		let pattern = Pattern::new("https://example.com");
+		assert!(pattern.matches("https://example.com"));
+		assert!(!pattern.matches("https://example.com:8080"));
+	}
+
+	#[test]
+	fn matches_wildcard_subdomain() {
+		let pattern = Pattern::new("https://*.example.com");
+		assert!(pattern.matches("https://foo.example.com"));
+		assert!(pattern.matches("https://foo.bar.example.com"));
+		assert!(!pattern.matches("https://example.com"));
+		assert!(!pattern.matches("https://example.com.evil.com"));
+	}
+
+	#[test]
+	fn matches_wildcard_anywhere() {
+		let pattern = Pattern::new("chrome-extension://*");
+		assert!(pattern.matches("chrome-extension://abcdefg"));
+		assert!(pattern.matches("chrome-extension://"));
+	}
+}
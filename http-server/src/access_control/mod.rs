@@ -30,18 +30,53 @@ pub(crate) mod cors;
 pub(crate) mod hosts;
 mod matcher;
 
+use std::time::Duration;
+
 use hosts::{AllowHosts, Host};
 
-use cors::{AccessControlAllowHeaders, AccessControlAllowOrigin};
+use cors::{AccessControlAllowHeaders, AccessControlAllowMethods, AccessControlAllowOrigin};
 use hyper::header;
 use jsonrpsee_utils::http_helpers;
 
+/// Error produced by [`AccessControlBuilder::build`] when the requested configuration is invalid.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AccessControlError {
+	/// `Access-Control-Allow-Credentials: true` was combined with a wildcard (`Any`) allowed
+	/// origin, which the CORS specification forbids.
+	CredentialsWithWildcardOrigin,
+	/// `Access-Control-Allow-Credentials: true` was combined with a wildcard (`Any`) allowed
+	/// methods configuration, which the CORS specification forbids.
+	CredentialsWithWildcardMethods,
+	/// A configured CORS origin or header does not parse into a valid HTTP header value.
+	InvalidHeaderValue(String),
+}
+
+impl std::fmt::Display for AccessControlError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::CredentialsWithWildcardOrigin => {
+				write!(f, "`Access-Control-Allow-Credentials: true` cannot be combined with a wildcard CORS origin")
+			}
+			Self::CredentialsWithWildcardMethods => {
+				write!(f, "`Access-Control-Allow-Credentials: true` cannot be combined with a wildcard CORS methods")
+			}
+			Self::InvalidHeaderValue(value) => write!(f, "`{}` is not a valid HTTP header value", value),
+		}
+	}
+}
+
+impl std::error::Error for AccessControlError {}
+
 /// Define access on control on HTTP layer.
 #[derive(Clone, Debug)]
 pub struct AccessControl {
 	allow_hosts: AllowHosts,
 	cors_allow_origin: Option<Vec<AccessControlAllowOrigin>>,
 	cors_allow_headers: AccessControlAllowHeaders,
+	cors_allow_methods: AccessControlAllowMethods,
+	cors_allow_credentials: bool,
+	cors_max_age: Option<Duration>,
+	cors_expose_headers: Option<Vec<String>>,
 	continue_on_invalid_cors: bool,
 }
 
@@ -66,6 +101,7 @@ impl AccessControl {
 				}
 				Null => header::HeaderValue::from_static("null"),
 				Any => header::HeaderValue::from_static("*"),
+				Pattern(_) => unreachable!("pattern matches are reflected as Value; qed"),
 			}
 		});
 		header == cors::AllowCors::Invalid && !self.continue_on_invalid_cors
@@ -84,6 +120,94 @@ impl AccessControl {
 		});
 		header == cors::AllowCors::Invalid && !self.continue_on_invalid_cors
 	}
+
+	/// Validate incoming preflight request by the requested CORS method.
+	pub fn deny_cors_method(&self, request: &hyper::Request<hyper::Body>) -> bool {
+		let requested_method = http_helpers::read_header_value(request.headers(), "access-control-request-method");
+		let method = cors::get_cors_allow_method(requested_method, &self.cors_allow_methods);
+		method == cors::AllowCors::Invalid && !self.continue_on_invalid_cors
+	}
+
+	/// Build a full CORS preflight response for an `OPTIONS` preflight request.
+	///
+	/// Returns `None` when `request` is not a preflight request, in which case normal
+	/// dispatch should proceed instead.
+	pub fn preflight_response(&self, request: &hyper::Request<hyper::Body>) -> Option<hyper::Response<hyper::Body>> {
+		if request.method() != hyper::Method::OPTIONS
+			|| http_helpers::read_header_value(request.headers(), "access-control-request-method").is_none()
+		{
+			return None;
+		}
+
+		let mut response = hyper::Response::builder()
+			.status(hyper::StatusCode::NO_CONTENT)
+			.body(hyper::Body::empty())
+			.expect("empty body with a valid status code; qed");
+
+		let origin = cors::get_cors_allow_origin(
+			http_helpers::read_header_value(request.headers(), "origin"),
+			http_helpers::read_header_value(request.headers(), "host"),
+			&self.cors_allow_origin,
+		);
+		if let cors::AllowCors::Ok(origin) = origin {
+			use self::cors::AccessControlAllowOrigin::*;
+			let value = match origin {
+				Value(ref val) => {
+					header::HeaderValue::from_str(val).unwrap_or_else(|_| header::HeaderValue::from_static("null"))
+				}
+				Null => header::HeaderValue::from_static("null"),
+				Any => header::HeaderValue::from_static("*"),
+				Pattern(_) => unreachable!("pattern matches are reflected as Value; qed"),
+			};
+			response.headers_mut().insert(header::ACCESS_CONTROL_ALLOW_ORIGIN, value);
+		}
+
+		response
+			.headers_mut()
+			.insert(header::ACCESS_CONTROL_ALLOW_METHODS, cors::allow_methods_header_value(&self.cors_allow_methods));
+
+		if self.cors_allow_credentials {
+			response
+				.headers_mut()
+				.insert(header::ACCESS_CONTROL_ALLOW_CREDENTIALS, header::HeaderValue::from_static("true"));
+		}
+
+		let headers = request.headers().keys().map(|name| name.as_str());
+		let requested_headers = http_helpers::read_header_values(request.headers(), "access-control-request-headers")
+			.filter_map(|val| val.to_str().ok())
+			.flat_map(|val| val.split(", "))
+			.flat_map(|val| val.split(','));
+		let allow_headers = cors::get_cors_allow_headers(headers, requested_headers, &self.cors_allow_headers, |name| {
+			header::HeaderValue::from_str(name).unwrap_or_else(|_| header::HeaderValue::from_static("unknown"))
+		});
+		if let cors::AllowCors::Ok(value) = allow_headers {
+			response.headers_mut().insert(header::ACCESS_CONTROL_ALLOW_HEADERS, value);
+		}
+
+		if let Some(max_age) = self.cors_max_age {
+			response
+				.headers_mut()
+				.insert(header::ACCESS_CONTROL_MAX_AGE, header::HeaderValue::from(max_age.as_secs()));
+		}
+
+		Some(response)
+	}
+
+	/// Append `Access-Control-Expose-Headers` to `response`, if configured.
+	///
+	/// Unlike [`preflight_response`](Self::preflight_response), this is meant to be applied to
+	/// the response of an actual (non-preflight) cross-origin request, so that browsers expose
+	/// the listed headers to the calling script.
+	pub fn apply_cors_expose_headers(&self, response: &mut hyper::Response<hyper::Body>) {
+		let expose_headers = match self.cors_expose_headers {
+			Some(ref expose_headers) => expose_headers,
+			None => return,
+		};
+
+		if let Ok(value) = header::HeaderValue::from_str(&expose_headers.join(", ")) {
+			response.headers_mut().insert(header::ACCESS_CONTROL_EXPOSE_HEADERS, value);
+		}
+	}
 }
 
 impl Default for AccessControl {
@@ -92,6 +216,10 @@ impl Default for AccessControl {
 			allow_hosts: AllowHosts::Any,
 			cors_allow_origin: None,
 			cors_allow_headers: AccessControlAllowHeaders::Any,
+			cors_allow_methods: AccessControlAllowMethods::Any,
+			cors_allow_credentials: false,
+			cors_max_age: None,
+			cors_expose_headers: None,
 			continue_on_invalid_cors: false,
 		}
 	}
@@ -103,6 +231,10 @@ pub struct AccessControlBuilder {
 	allow_hosts: AllowHosts,
 	cors_allow_origin: Option<Vec<AccessControlAllowOrigin>>,
 	cors_allow_headers: AccessControlAllowHeaders,
+	cors_allow_methods: AccessControlAllowMethods,
+	cors_allow_credentials: bool,
+	cors_max_age: Option<Duration>,
+	cors_expose_headers: Option<Vec<String>>,
 	continue_on_invalid_cors: bool,
 }
 
@@ -112,6 +244,10 @@ impl Default for AccessControlBuilder {
 			allow_hosts: AllowHosts::Any,
 			cors_allow_origin: None,
 			cors_allow_headers: AccessControlAllowHeaders::Any,
+			cors_allow_methods: AccessControlAllowMethods::Any,
+			cors_allow_credentials: false,
+			cors_max_age: None,
+			cors_expose_headers: None,
 			continue_on_invalid_cors: false,
 		}
 	}
@@ -162,19 +298,113 @@ impl AccessControlBuilder {
 		self
 	}
 
+	/// Configure a single HTTP method that is allowed for CORS requests.
+	pub fn cors_allow_method(self, method: hyper::Method) -> Self {
+		self.cors_allow_methods(std::iter::once(method))
+	}
+
+	/// Configure which HTTP methods are allowed for CORS requests.
+	pub fn cors_allow_methods(mut self, methods: impl IntoIterator<Item = hyper::Method>) -> Self {
+		let allow_methods = match self.cors_allow_methods {
+			AccessControlAllowMethods::Any => methods.into_iter().collect(),
+			AccessControlAllowMethods::Only(mut allow_methods) => {
+				allow_methods.extend(methods);
+				allow_methods
+			}
+		};
+		self.cors_allow_methods = AccessControlAllowMethods::Only(allow_methods);
+		self
+	}
+
+	/// Configure how long the result of a preflight request can be cached, via
+	/// `Access-Control-Max-Age`.
+	pub fn cors_max_age(mut self, max_age: Duration) -> Self {
+		self.cors_max_age = Some(max_age);
+		self
+	}
+
+	/// Configure a single response header to expose via `Access-Control-Expose-Headers`.
+	pub fn cors_expose_header(self, header: String) -> Self {
+		self.cors_expose_headers(vec![header])
+	}
+
+	/// Configure which response headers to expose via `Access-Control-Expose-Headers`.
+	pub fn cors_expose_headers(mut self, headers: Vec<String>) -> Self {
+		let expose_headers = match self.cors_expose_headers {
+			Some(mut expose_headers) => {
+				expose_headers.extend(headers);
+				expose_headers
+			}
+			None => headers,
+		};
+		self.cors_expose_headers = Some(expose_headers);
+		self
+	}
+
 	/// Enable or disable to continue with invalid CORS.
 	pub fn continue_on_invalid_cors(mut self, continue_on_invalid_cors: bool) -> Self {
 		self.continue_on_invalid_cors = continue_on_invalid_cors;
 		self
 	}
 
+	/// Enable or disable `Access-Control-Allow-Credentials`.
+	///
+	/// Note that per the CORS specification, credentials cannot be allowed together with a
+	/// wildcard (`Any`) CORS origin; [`build`](Self::build) rejects that combination.
+	pub fn cors_allow_credentials(mut self, cors_allow_credentials: bool) -> Self {
+		self.cors_allow_credentials = cors_allow_credentials;
+		self
+	}
+
 	/// Build.
-	pub fn build(self) -> AccessControl {
-		AccessControl {
+	pub fn build(self) -> Result<AccessControl, AccessControlError> {
+		if self.cors_allow_credentials {
+			let has_wildcard_origin = self
+				.cors_allow_origin
+				.as_ref()
+				.map(|origins| origins.iter().any(|origin| matches!(origin, AccessControlAllowOrigin::Any)))
+				.unwrap_or(false);
+			if has_wildcard_origin {
+				return Err(AccessControlError::CredentialsWithWildcardOrigin);
+			}
+
+			if matches!(self.cors_allow_methods, AccessControlAllowMethods::Any) {
+				return Err(AccessControlError::CredentialsWithWildcardMethods);
+			}
+		}
+
+		if let Some(ref origins) = self.cors_allow_origin {
+			for origin in origins {
+				if let AccessControlAllowOrigin::Value(ref val) = origin {
+					header::HeaderValue::from_str(val)
+						.map_err(|_| AccessControlError::InvalidHeaderValue(val.clone()))?;
+				}
+			}
+		}
+
+		if let AccessControlAllowHeaders::Only(ref headers) = self.cors_allow_headers {
+			for header_name in headers {
+				header::HeaderValue::from_str(header_name)
+					.map_err(|_| AccessControlError::InvalidHeaderValue(header_name.clone()))?;
+			}
+		}
+
+		if let Some(ref expose_headers) = self.cors_expose_headers {
+			for header_name in expose_headers {
+				header::HeaderValue::from_str(header_name)
+					.map_err(|_| AccessControlError::InvalidHeaderValue(header_name.clone()))?;
+			}
+		}
+
+		Ok(AccessControl {
 			allow_hosts: self.allow_hosts,
 			cors_allow_origin: self.cors_allow_origin,
 			cors_allow_headers: self.cors_allow_headers,
+			cors_allow_methods: self.cors_allow_methods,
+			cors_allow_credentials: self.cors_allow_credentials,
+			cors_max_age: self.cors_max_age,
+			cors_expose_headers: self.cors_expose_headers,
 			continue_on_invalid_cors: self.continue_on_invalid_cors,
-		}
+		})
 	}
 }